@@ -0,0 +1,165 @@
+//
+// APNG playback: walks every frame of an animated PNG, resolving
+// dispose/blend ops against an accumulated RGBA canvas, and emits a
+// multi-frame C array plus a parallel per-frame delay table so the
+// Teensy sketch can play the animation back with correct timing.
+//
+
+use std::fs::File;
+
+use crate::dither;
+use crate::luma;
+use crate::pack;
+use crate::transform;
+use crate::{apply_transforms, Args};
+
+// One fully-resolved, display-ready frame.
+struct Frame {
+    delay_ms: u32,
+    canvas: Vec<[u8; 4]>,
+}
+
+fn composite(
+    canvas: &mut [[u8; 4]],
+    canvas_w: usize,
+    frame: &[[u8; 4]],
+    fw: usize,
+    fh: usize,
+    x_off: usize,
+    y_off: usize,
+    blend_op: png::BlendOp,
+) {
+    for y in 0..fh {
+        for x in 0..fw {
+            let src = frame[y * fw + x];
+            let dst_idx = (y + y_off) * canvas_w + (x + x_off);
+            canvas[dst_idx] = match blend_op {
+                png::BlendOp::Source => src,
+                png::BlendOp::Over => {
+                    // Unpremultiplied "over": the destination's own
+                    // alpha has to weight its contribution too, or a
+                    // transparent destination (e.g. just cleared by
+                    // DisposeOp::Background) gets treated as opaque
+                    // black instead of see-through.
+                    let [dr, dg, db, da] = canvas[dst_idx];
+                    let a = src[3] as u32;
+                    let da = da as u32;
+                    let out_a = a + da * (255 - a) / 255;
+                    let blend = |s: u8, d: u8| -> u8 {
+                        if out_a == 0 {
+                            0
+                        } else {
+                            ((s as u32 * a + d as u32 * da * (255 - a) / 255) / out_a) as u8
+                        }
+                    };
+                    [blend(src[0], dr), blend(src[1], dg), blend(src[2], db), out_a.min(255) as u8]
+                }
+            };
+        }
+    }
+}
+
+fn clear(canvas: &mut [[u8; 4]], canvas_w: usize, fw: usize, fh: usize, x_off: usize, y_off: usize) {
+    for y in 0..fh {
+        for x in 0..fw {
+            canvas[(y + y_off) * canvas_w + (x + x_off)] = [0, 0, 0, 0];
+        }
+    }
+}
+
+// Decodes every frame of the already-opened APNG `reader`/`info`,
+// applies the same background/dither/transform/addressing options as
+// the single-frame path, and prints the resulting C array and delay
+// table to stdout under `stem`.
+pub fn emit(reader: &mut png::Reader<File>, info: &png::Info, stem: &str, args: &Args) {
+    let background = args.background;
+    let w = info.width as usize;
+    let h = info.height as usize;
+    let num_frames = info
+        .animation_control
+        .map(|ac| ac.num_frames)
+        .unwrap_or(1) as usize;
+
+    let mut canvas = vec![[background[0], background[1], background[2], 255]; w * h];
+    let mut frames = Vec::with_capacity(num_frames);
+
+    for _ in 0..num_frames {
+        let mut raw_buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut raw_buf).unwrap();
+        // `info()` keeps the header's color type/bit depth; describe the
+        // frame by the post-EXPAND format the buffer actually holds.
+        let (color_type, bit_depth) = reader.output_color_type();
+        let mut frame_info = reader.info().clone();
+        frame_info.color_type = color_type;
+        frame_info.bit_depth = bit_depth;
+        let fc = frame_info.frame_control;
+
+        let (fw, fh, x_off, y_off, delay_ms, dispose_op, blend_op) = match fc {
+            Some(fc) => (
+                fc.width as usize,
+                fc.height as usize,
+                fc.x_offset as usize,
+                fc.y_offset as usize,
+                if fc.delay_den == 0 {
+                    (fc.delay_num as u32) * 10
+                } else {
+                    fc.delay_num as u32 * 1000 / fc.delay_den as u32
+                },
+                fc.dispose_op,
+                fc.blend_op,
+            ),
+            // Default image with no fcTL: draw once, full-canvas, no delay.
+            None => (w, h, 0, 0, 0, png::DisposeOp::None, png::BlendOp::Source),
+        };
+
+        let rgba = luma::to_rgba(&frame_info, &raw_buf, fw, fh);
+        let pre_dispose = canvas.clone();
+        composite(&mut canvas, w, &rgba, fw, fh, x_off, y_off, blend_op);
+
+        frames.push(Frame {
+            delay_ms,
+            canvas: canvas.clone(),
+        });
+
+        match dispose_op {
+            png::DisposeOp::None => {}
+            png::DisposeOp::Background => clear(&mut canvas, w, fw, fh, x_off, y_off),
+            png::DisposeOp::Previous => {
+                for y in 0..fh {
+                    for x in 0..fw {
+                        let idx = (y + y_off) * w + (x + x_off);
+                        canvas[idx] = pre_dispose[idx];
+                    }
+                }
+            }
+        }
+    }
+
+    let (out_w, out_h) = transform::rotated_dims(args.rotation, w as u32, h as u32);
+    let pages = (out_h + 7) / 8;
+    let bytes_per_frame = out_w * pages;
+
+    println!(
+        "static const char {}[{}][{}] = {{",
+        stem,
+        frames.len(),
+        bytes_per_frame
+    );
+    for frame in &frames {
+        let gray = luma::flatten(&frame.canvas, background);
+        let (fw, fh, mut gray) = apply_transforms(args, w as u32, h as u32, gray);
+        if let Some(mode) = args.dither_mode {
+            dither::apply(mode, &mut gray, fw, fh);
+        }
+        println!("    {{");
+        pack::print_pages(&gray, fw, fh, args.addressing);
+        println!("    }},");
+    }
+    println!("}};");
+
+    print!("static const unsigned {}_delays[] = {{ ", stem);
+    for frame in &frames {
+        print!("{}, ", frame.delay_ms);
+    }
+    println!("}};");
+}
@@ -0,0 +1,135 @@
+//
+// Minimal BMP decoder: just enough of BITMAPFILEHEADER/BITMAPINFOHEADER
+// to read the 1/8/24-bit-per-pixel images embedded-graphics' tinybmp
+// also targets. No compression, no OS/2 headers.
+//
+
+use crate::luma::luma;
+
+fn u32_at(buf: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes(buf[off..off + 4].try_into().unwrap())
+}
+
+fn i32_at(buf: &[u8], off: usize) -> i32 {
+    i32::from_le_bytes(buf[off..off + 4].try_into().unwrap())
+}
+
+fn u16_at(buf: &[u8], off: usize) -> u16 {
+    u16::from_le_bytes(buf[off..off + 2].try_into().unwrap())
+}
+
+// Returns `(width, height, grayscale_buf)` with the buffer in
+// top-down raster order, matching the rest of the pipeline.
+pub fn load(data: &[u8]) -> (u32, u32, Vec<u8>) {
+    assert_eq!(&data[0..2], b"BM", "Not a BMP file");
+    let data_offset = u32_at(data, 10) as usize;
+    let dib_header_size = u32_at(data, 14);
+    assert!(dib_header_size >= 40, "Unsupported BMP DIB header");
+
+    let width = i32_at(data, 18);
+    let raw_height = i32_at(data, 22);
+    let top_down = raw_height < 0;
+    let height = raw_height.unsigned_abs();
+    let bpp = u16_at(data, 28);
+    let width = width as u32;
+
+    let row_bytes = ((width as usize * bpp as usize + 31) / 32) * 4;
+
+    // Palette, if any, sits right after the DIB header.
+    let palette_offset = 14 + dib_header_size as usize;
+    let read_palette_entry = |idx: usize| -> (u8, u8, u8) {
+        let off = palette_offset + idx * 4;
+        (data[off + 2], data[off + 1], data[off]) // BMP palette is BGRA.
+    };
+
+    let mut out = vec![0u8; width as usize * height as usize];
+    for row in 0..height {
+        // BMP rows are stored bottom-up unless the height is negative.
+        let src_row = if top_down { row } else { height - 1 - row };
+        let row_start = data_offset + src_row as usize * row_bytes;
+
+        for x in 0..width {
+            let gray = match bpp {
+                1 => {
+                    let byte = data[row_start + (x / 8) as usize];
+                    let bit = 7 - (x % 8);
+                    let idx = ((byte >> bit) & 1) as usize;
+                    let (r, g, b) = read_palette_entry(idx);
+                    luma(r, g, b)
+                }
+                8 => {
+                    let idx = data[row_start + x as usize] as usize;
+                    let (r, g, b) = read_palette_entry(idx);
+                    luma(r, g, b)
+                }
+                24 => {
+                    let off = row_start + x as usize * 3;
+                    let (b, g, r) = (data[off], data[off + 1], data[off + 2]);
+                    luma(r, g, b)
+                }
+                other => panic!("Unsupported BMP bit depth: {}", other),
+            };
+            out[(row * width + x) as usize] = gray;
+        }
+    }
+
+    (width, height, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_header(data_offset: u32, file_size: u32) -> Vec<u8> {
+        let mut h = Vec::new();
+        h.extend_from_slice(b"BM");
+        h.extend_from_slice(&file_size.to_le_bytes());
+        h.extend_from_slice(&[0u8; 4]); // reserved
+        h.extend_from_slice(&data_offset.to_le_bytes());
+        h
+    }
+
+    fn dib_header(width: i32, height: i32, bpp: u16) -> Vec<u8> {
+        let mut h = Vec::new();
+        h.extend_from_slice(&40u32.to_le_bytes()); // header size
+        h.extend_from_slice(&width.to_le_bytes());
+        h.extend_from_slice(&height.to_le_bytes());
+        h.extend_from_slice(&1u16.to_le_bytes()); // planes
+        h.extend_from_slice(&bpp.to_le_bytes());
+        h.extend_from_slice(&[0u8; 24]); // compression, sizes, ppm, palette counts
+        h
+    }
+
+    #[test]
+    fn loads_24bpp_bottom_up() {
+        // 2x2, top row (0, 64), bottom row (128, 255) — equal R=G=B so
+        // luma comes back out exactly.
+        let data_offset = 54u32;
+        let mut data = file_header(data_offset, data_offset + 16);
+        data.extend(dib_header(2, 2, 24)); // height > 0: bottom-up.
+        // Bottom row first: (128,128,128), (255,255,255), then 2 padding bytes (6 pixel bytes, row_bytes=8).
+        data.extend_from_slice(&[128, 128, 128, 255, 255, 255, 0, 0]);
+        // Then top row: (0,0,0), (64,64,64).
+        data.extend_from_slice(&[0, 0, 0, 64, 64, 64, 0, 0]);
+
+        let (w, h, buf) = load(&data);
+        assert_eq!((w, h), (2, 2));
+        assert_eq!(buf, vec![0, 64, 128, 255]);
+    }
+
+    #[test]
+    fn loads_1bpp_indexed_row() {
+        // 8x1, palette {0: black, 1: white}, pixel byte 0b10110010.
+        let palette_size = 8u32; // 2 entries * 4 bytes (BGRA)
+        let data_offset = 14 + 40 + palette_size;
+        let mut data = file_header(data_offset, data_offset + 4);
+        data.extend(dib_header(8, 1, 1));
+        data.extend_from_slice(&[0, 0, 0, 0]); // palette[0] = black
+        data.extend_from_slice(&[255, 255, 255, 0]); // palette[1] = white
+        data.extend_from_slice(&[0b10110010, 0, 0, 0]); // row padded to 4 bytes.
+
+        let (w, h, buf) = load(&data);
+        assert_eq!((w, h), (8, 1));
+        assert_eq!(buf, vec![255, 0, 255, 255, 0, 0, 255, 0]);
+    }
+}
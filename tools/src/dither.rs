@@ -0,0 +1,159 @@
+//
+// Error-diffusion and ordered dithering for the grayscale -> 1-bit
+// conversion done before page-packing. Each mode operates on a
+// mutable copy of the grayscale buffer and quantizes in place, so the
+// existing `>= 0x80` threshold in the packing loop keeps working
+// unchanged afterwards.
+//
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DitherMode {
+    FloydSteinberg,
+    Atkinson,
+    Ordered,
+}
+
+impl DitherMode {
+    pub fn from_str(s: &str) -> Option<DitherMode> {
+        match s {
+            "" | "floyd-steinberg" | "fs" => Some(DitherMode::FloydSteinberg),
+            "atkinson" => Some(DitherMode::Atkinson),
+            "ordered" => Some(DitherMode::Ordered),
+            _ => None,
+        }
+    }
+}
+
+// 8x8 Bayer threshold matrix (values 0..63); `ordered` scales it by 4
+// to the 0..256 range of the grayscale samples.
+const BAYER_8X8: [[u16; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+fn quantize(old: i16) -> (u8, i16) {
+    if old >= 0x80 {
+        (255, old - 255)
+    } else {
+        (0, old)
+    }
+}
+
+fn add_err(acc: &mut [i16], w: u32, h: u32, x: i64, y: i64, err: i16, weight: i16, divisor: i16) {
+    if x < 0 || y < 0 || x >= w as i64 || y >= h as i64 {
+        return;
+    }
+    let idx = (y as u32 * w + x as u32) as usize;
+    let delta = (err as i32 * weight as i32 / divisor as i32) as i16;
+    acc[idx] = (acc[idx] + delta).clamp(0, 255);
+}
+
+// Classic Floyd-Steinberg diffusion: right 7/16, below-left 3/16,
+// below 5/16, below-right 1/16.
+fn floyd_steinberg(buf: &mut [i16], w: u32, h: u32) {
+    for y in 0..h as i64 {
+        for x in 0..w as i64 {
+            let idx = (y as u32 * w + x as u32) as usize;
+            let (new, err) = quantize(buf[idx]);
+            buf[idx] = new as i16;
+            add_err(buf, w, h, x + 1, y, err, 7, 16);
+            add_err(buf, w, h, x - 1, y + 1, err, 3, 16);
+            add_err(buf, w, h, x, y + 1, err, 5, 16);
+            add_err(buf, w, h, x + 1, y + 1, err, 1, 16);
+        }
+    }
+}
+
+// Atkinson diffusion: only 6/8 of the error is distributed, the rest
+// is deliberately lost, which raises contrast at the cost of detail
+// in shadows/highlights.
+fn atkinson(buf: &mut [i16], w: u32, h: u32) {
+    for y in 0..h as i64 {
+        for x in 0..w as i64 {
+            let idx = (y as u32 * w + x as u32) as usize;
+            let (new, err) = quantize(buf[idx]);
+            buf[idx] = new as i16;
+            add_err(buf, w, h, x + 1, y, err, 1, 8);
+            add_err(buf, w, h, x + 2, y, err, 1, 8);
+            add_err(buf, w, h, x - 1, y + 1, err, 1, 8);
+            add_err(buf, w, h, x, y + 1, err, 1, 8);
+            add_err(buf, w, h, x + 1, y + 1, err, 1, 8);
+            add_err(buf, w, h, x, y + 2, err, 1, 8);
+        }
+    }
+}
+
+fn ordered(buf: &mut [i16], w: u32, h: u32) {
+    for y in 0..h {
+        for x in 0..w {
+            let idx = (y * w + x) as usize;
+            // Half-step offset so the matrix's 0 cell never flips a
+            // pure-black pixel to white, and its 63 cell never fails
+            // to flip a pure-white one.
+            let threshold =
+                (BAYER_8X8[(y % 8) as usize][(x % 8) as usize] as f32 + 0.5) * 4.0;
+            buf[idx] = if buf[idx] as f32 >= threshold { 255 } else { 0 };
+        }
+    }
+}
+
+// Dithers `buf` (raster-order grayscale samples, one byte each)
+// in place according to `mode`.
+pub fn apply(mode: DitherMode, buf: &mut [u8], w: u32, h: u32) {
+    let mut acc: Vec<i16> = buf.iter().map(|&b| b as i16).collect();
+    match mode {
+        DitherMode::FloydSteinberg => floyd_steinberg(&mut acc, w, h),
+        DitherMode::Atkinson => atkinson(&mut acc, w, h),
+        DitherMode::Ordered => ordered(&mut acc, w, h),
+    }
+    for (dst, &src) in buf.iter_mut().zip(acc.iter()) {
+        *dst = src.clamp(0, 255) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_modes_quantize_to_black_or_white() {
+        let buf = vec![0u8, 64, 128, 192];
+        for mode in [DitherMode::FloydSteinberg, DitherMode::Atkinson, DitherMode::Ordered] {
+            let mut frame = buf.clone();
+            apply(mode, &mut frame, 2, 2);
+            for &b in &frame {
+                assert!(b == 0 || b == 255, "{:?} produced non-binary byte {}", mode, b);
+            }
+        }
+    }
+
+    #[test]
+    fn ordered_leaves_solid_black_untouched() {
+        // Regression test: the 0..63 Bayer matrix must never threshold
+        // a pure-black pixel (0) to white.
+        let mut buf = vec![0u8; 64];
+        apply(DitherMode::Ordered, &mut buf, 8, 8);
+        assert!(buf.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn ordered_leaves_solid_white_untouched() {
+        let mut buf = vec![255u8; 64];
+        apply(DitherMode::Ordered, &mut buf, 8, 8);
+        assert!(buf.iter().all(|&b| b == 255));
+    }
+
+    #[test]
+    fn from_str_parses_known_modes() {
+        assert_eq!(DitherMode::from_str(""), Some(DitherMode::FloydSteinberg));
+        assert_eq!(DitherMode::from_str("atkinson"), Some(DitherMode::Atkinson));
+        assert_eq!(DitherMode::from_str("ordered"), Some(DitherMode::Ordered));
+        assert_eq!(DitherMode::from_str("bogus"), None);
+    }
+}
@@ -0,0 +1,80 @@
+//
+// Dispatches a source image path to the right decoder based on its
+// extension (falling back to magic bytes if the extension is missing
+// or unrecognised), and always hands back plain 8-bit grayscale.
+// PNG's APNG animation support is handled separately in `main`, since
+// that needs the live `png::Reader` rather than a flat buffer.
+//
+
+use std::fs;
+use std::path::Path;
+
+use crate::bmp;
+use crate::luma;
+use crate::ppm;
+
+enum Format {
+    Png,
+    Bmp,
+    Ppm,
+}
+
+fn sniff(data: &[u8]) -> Format {
+    if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        Format::Png
+    } else if data.starts_with(b"BM") {
+        Format::Bmp
+    } else if data.starts_with(b"P5") || data.starts_with(b"P6") {
+        Format::Ppm
+    } else {
+        panic!("Unrecognised image format (not PNG, BMP or PPM)");
+    }
+}
+
+fn format_for(path: &Path, data: &[u8]) -> Format {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => Format::Png,
+        Some("bmp") => Format::Bmp,
+        Some("ppm") | Some("pgm") => Format::Ppm,
+        _ => sniff(data),
+    }
+}
+
+// Whether `path` is a PNG, so `main` knows whether it's worth peeking
+// at the animation control chunk before falling through to the
+// generic (single-frame) loader below.
+pub fn is_png(path: &Path) -> bool {
+    let data = fs::read(path).unwrap();
+    matches!(format_for(path, &data), Format::Png)
+}
+
+// Loads `path`, converting whatever format it turns out to be into
+// `(width, height, grayscale_buf)`.
+pub fn load_grayscale(path: &Path, background: [u8; 3]) -> (u32, u32, Vec<u8>) {
+    let data = fs::read(path).unwrap();
+
+    match format_for(path, &data) {
+        Format::Png => {
+            let mut decoder = png::Decoder::new(&data[..]);
+            // Unpack indexed color and sub-8-bit depths to plain 8bpp
+            // (and tRNS to a real alpha channel) before luma ever sees
+            // the buffer, rather than leaving it to reinterpret
+            // packed/palette bytes itself.
+            decoder.set_transformations(png::Transformations::EXPAND);
+            // `luma::to_grayscale` takes the full `Info` from
+            // `reader.info()`, but that keeps the header's color type and
+            // bit depth; overwrite them with the post-EXPAND ones from
+            // `OutputInfo`, which describe the buffer actually decoded.
+            let (output_info, mut reader) = decoder.read_info().unwrap();
+            let mut raw_buf = vec![0; output_info.buffer_size()];
+            reader.next_frame(&mut raw_buf).unwrap();
+            let mut info = reader.info().clone();
+            info.color_type = output_info.color_type;
+            info.bit_depth = output_info.bit_depth;
+            let buf = luma::to_grayscale(&info, &raw_buf, background);
+            (output_info.width, output_info.height, buf)
+        }
+        Format::Bmp => bmp::load(&data),
+        Format::Ppm => ppm::load(&data),
+    }
+}
@@ -0,0 +1,158 @@
+//
+// Collapses whatever color type/bit depth a PNG decodes to down to an
+// 8-bit grayscale buffer, so the rest of the pipeline only ever has
+// to deal with one pixel format.
+//
+
+// Rec.709 luma weights.
+const LUMA_R: f32 = 0.2126;
+const LUMA_G: f32 = 0.7152;
+const LUMA_B: f32 = 0.0722;
+
+pub(crate) fn luma(r: u8, g: u8, b: u8) -> u8 {
+    (LUMA_R * r as f32 + LUMA_G * g as f32 + LUMA_B * b as f32).round() as u8
+}
+
+// Alpha-composites an RGBA sample over `background`, then takes the
+// luma of the result.
+fn composite_luma(r: u8, g: u8, b: u8, a: u8, background: [u8; 3]) -> u8 {
+    let blend = |fg: u8, bg: u8| -> u8 {
+        let a = a as u32;
+        ((fg as u32 * a + bg as u32 * (255 - a)) / 255) as u8
+    };
+    luma(
+        blend(r, background[0]),
+        blend(g, background[1]),
+        blend(b, background[2]),
+    )
+}
+
+// Converts a decoded PNG buffer (whatever color type/bit depth it
+// came in as) to an 8-bit grayscale buffer, compositing any alpha
+// channel over `background` first.
+//
+// Assumes the decoder was opened with `Transformations::EXPAND`, so
+// indexed color and sub-8-bit depths have already been unpacked to
+// 8bpp (and any tRNS chunk turned into a real alpha channel) before
+// this ever sees the buffer — it never has to unpack palette indices
+// itself.
+pub fn to_grayscale(
+    info: &png::Info,
+    buf: &[u8],
+    background: [u8; 3],
+) -> Vec<u8> {
+    let w = info.width as usize;
+    let h = info.height as usize;
+    let mut out = Vec::with_capacity(w * h);
+
+    match (info.color_type, info.bit_depth) {
+        (png::ColorType::Grayscale, png::BitDepth::Eight) => {
+            out.extend_from_slice(&buf[..w * h]);
+        }
+        (png::ColorType::Grayscale, png::BitDepth::Sixteen) => {
+            for px in buf.chunks_exact(2).take(w * h) {
+                out.push(px[0]); // Big-endian 16-bit: take the high byte.
+            }
+        }
+        (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => {
+            for px in buf.chunks_exact(2).take(w * h) {
+                let g = px[0];
+                let a = px[1];
+                out.push(composite_luma(g, g, g, a, background));
+            }
+        }
+        (png::ColorType::RGB, png::BitDepth::Eight) => {
+            for px in buf.chunks_exact(3).take(w * h) {
+                out.push(luma(px[0], px[1], px[2]));
+            }
+        }
+        (png::ColorType::RGB, png::BitDepth::Sixteen) => {
+            for px in buf.chunks_exact(6).take(w * h) {
+                out.push(luma(px[0], px[2], px[4]));
+            }
+        }
+        (png::ColorType::RGBA, png::BitDepth::Eight) => {
+            for px in buf.chunks_exact(4).take(w * h) {
+                out.push(composite_luma(px[0], px[1], px[2], px[3], background));
+            }
+        }
+        (png::ColorType::RGBA, png::BitDepth::Sixteen) => {
+            for px in buf.chunks_exact(8).take(w * h) {
+                out.push(composite_luma(px[0], px[2], px[4], px[6], background));
+            }
+        }
+        (color_type, bit_depth) => {
+            panic!(
+                "Unsupported PNG format: {:?}/{:?} (expected EXPAND transformation to have \
+                 unpacked this already)",
+                color_type, bit_depth
+            );
+        }
+    }
+
+    out
+}
+
+// Converts a decoded PNG buffer to one RGBA quad per pixel, without
+// compositing out the alpha channel. Used where the caller needs to
+// do its own layer compositing (e.g. APNG frame blending) before
+// flattening to grayscale.
+pub fn to_rgba(info: &png::Info, buf: &[u8], w: usize, h: usize) -> Vec<[u8; 4]> {
+    let mut out = Vec::with_capacity(w * h);
+
+    match (info.color_type, info.bit_depth) {
+        (png::ColorType::Grayscale, png::BitDepth::Eight) => {
+            for &g in buf.iter().take(w * h) {
+                out.push([g, g, g, 255]);
+            }
+        }
+        (png::ColorType::Grayscale, png::BitDepth::Sixteen) => {
+            for px in buf.chunks_exact(2).take(w * h) {
+                out.push([px[0], px[0], px[0], 255]);
+            }
+        }
+        (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => {
+            for px in buf.chunks_exact(2).take(w * h) {
+                out.push([px[0], px[0], px[0], px[1]]);
+            }
+        }
+        (png::ColorType::RGB, png::BitDepth::Eight) => {
+            for px in buf.chunks_exact(3).take(w * h) {
+                out.push([px[0], px[1], px[2], 255]);
+            }
+        }
+        (png::ColorType::RGB, png::BitDepth::Sixteen) => {
+            for px in buf.chunks_exact(6).take(w * h) {
+                out.push([px[0], px[2], px[4], 255]);
+            }
+        }
+        (png::ColorType::RGBA, png::BitDepth::Eight) => {
+            for px in buf.chunks_exact(4).take(w * h) {
+                out.push([px[0], px[1], px[2], px[3]]);
+            }
+        }
+        (png::ColorType::RGBA, png::BitDepth::Sixteen) => {
+            for px in buf.chunks_exact(8).take(w * h) {
+                out.push([px[0], px[2], px[4], px[6]]);
+            }
+        }
+        (color_type, bit_depth) => {
+            panic!(
+                "Unsupported PNG format: {:?}/{:?} (expected EXPAND transformation to have \
+                 unpacked this already)",
+                color_type, bit_depth
+            );
+        }
+    }
+
+    out
+}
+
+// Flattens an RGBA canvas to 8-bit grayscale, compositing over
+// `background`.
+pub fn flatten(canvas: &[[u8; 4]], background: [u8; 3]) -> Vec<u8> {
+    canvas
+        .iter()
+        .map(|&[r, g, b, a]| composite_luma(r, g, b, a, background))
+        .collect()
+}
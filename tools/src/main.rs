@@ -3,46 +3,188 @@
 // on an SSD 1780 display.
 //
 
+mod anim;
+mod bmp;
+mod dither;
+mod loader;
+mod luma;
+mod pack;
+mod ppm;
+mod raw;
+mod transform;
+
 use std::env;
 use std::path::Path;
 use std::fs::File;
 
-fn main() {
-    let mut args = env::args();
-    assert_eq!(args.len(), 2);
-    let file_name_str = args.nth(1).unwrap();
-    let file_name = Path::new(&file_name_str);
+use dither::DitherMode;
+use pack::Addressing;
+use transform::Rotation;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    CArray,
+    Raw,
+}
+
+struct Args {
+    file_name: String,
+    dither_mode: Option<DitherMode>,
+    background: [u8; 3],
+    format: OutputFormat,
+    output: Option<String>,
+    signed: bool,
+    raw_header: bool,
+    rotation: Rotation,
+    flip_h: bool,
+    flip_v: bool,
+    invert: bool,
+    addressing: Addressing,
+}
 
-    let decoder = png::Decoder::new(File::open(file_name).unwrap());
-    let (info, mut reader) = decoder.read_info().unwrap();
-    // Allocate the output buffer.
-    let mut buf = vec![0; info.buffer_size()];
-    // Read the next frame. An APNG might contain multiple frames.
-    reader.next_frame(&mut buf).unwrap();
+// Parses an "RRGGBB" hex triple as used by `--background`.
+fn parse_hex_color(s: &str) -> [u8; 3] {
+    assert_eq!(s.len(), 6, "--background expects an RRGGBB hex triple");
+    let byte = |i: usize| u8::from_str_radix(&s[i..i + 2], 16).expect("Invalid hex in --background");
+    [byte(0), byte(2), byte(4)]
+}
+
+// Pulls `--dither[=mode]`, `--background=RRGGBB`, `--format raw`,
+// `--output <path>`, `--signed`, `--header`, `--rotate`, `--flip-h`,
+// `--flip-v`, `--invert` and `--vertical-addressing` out of the
+// argument list, leaving the (single) positional file name behind.
+fn parse_args() -> Args {
+    let mut file_name = None;
+    let mut dither_mode = None;
+    let mut background = [0, 0, 0];
+    let mut format = OutputFormat::CArray;
+    let mut output = None;
+    let mut signed = false;
+    let mut raw_header = false;
+    let mut rotation = Rotation::R0;
+    let mut flip_h = false;
+    let mut flip_v = false;
+    let mut invert = false;
+    let mut addressing = Addressing::Page;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if let Some(mode_str) = arg.strip_prefix("--dither") {
+            let mode_str = mode_str.strip_prefix('=').unwrap_or(mode_str);
+            dither_mode = Some(
+                DitherMode::from_str(mode_str)
+                    .unwrap_or_else(|| panic!("Unknown dither mode: {}", mode_str)),
+            );
+        } else if let Some(color_str) = arg.strip_prefix("--background=") {
+            background = parse_hex_color(color_str);
+        } else if arg == "--format" {
+            let fmt = args.next().expect("--format needs an argument");
+            format = match fmt.as_str() {
+                "c" => OutputFormat::CArray,
+                "raw" => OutputFormat::Raw,
+                _ => panic!("Unknown --format: {}", fmt),
+            };
+        } else if arg == "--output" {
+            output = Some(args.next().expect("--output needs an argument"));
+        } else if arg == "--signed" {
+            signed = true;
+        } else if arg == "--header" {
+            raw_header = true;
+        } else if arg == "--rotate" {
+            let deg = args.next().expect("--rotate needs an argument");
+            rotation = Rotation::from_str(&deg)
+                .unwrap_or_else(|| panic!("--rotate must be one of 0, 90, 180, 270, got {}", deg));
+        } else if arg == "--flip-h" {
+            flip_h = true;
+        } else if arg == "--flip-v" {
+            flip_v = true;
+        } else if arg == "--invert" {
+            invert = true;
+        } else if arg == "--vertical-addressing" {
+            addressing = Addressing::Vertical;
+        } else {
+            assert!(file_name.is_none(), "Only one input file is supported");
+            file_name = Some(arg);
+        }
+    }
+    Args {
+        file_name: file_name.expect(
+            "Usage: image2teensy [--dither[=mode]] [--background=RRGGBB] \
+             [--format c|raw] [--output <path>] [--signed] [--header] \
+             [--rotate 0|90|180|270] [--flip-h] [--flip-v] [--invert] \
+             [--vertical-addressing] <file.png>",
+        ),
+        dither_mode,
+        background,
+        format,
+        output,
+        signed,
+        raw_header,
+        rotation,
+        flip_h,
+        flip_v,
+        invert,
+        addressing,
+    }
+}
 
-    assert_eq!(info.color_type, png::ColorType::Grayscale);
-    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+// Applies the geometric transforms, in the order a user would expect
+// to compose them: rotate to the target orientation, then mirror,
+// then invert tone.
+fn apply_transforms(args: &Args, w: u32, h: u32, buf: Vec<u8>) -> (u32, u32, Vec<u8>) {
+    let (w, h, mut buf) = transform::rotate(args.rotation, w, h, &buf);
+    if args.flip_h {
+        buf = transform::flip_h(w, h, &buf);
+    }
+    if args.flip_v {
+        buf = transform::flip_v(w, h, &buf);
+    }
+    if args.invert {
+        transform::invert(&mut buf);
+    }
+    (w, h, buf)
+}
 
+fn main() {
+    let args = parse_args();
+    let file_name = Path::new(&args.file_name);
     let stem = file_name.file_stem().unwrap().to_str().unwrap();
-    println!("static const char {}[] = {{", stem);
-
-    // Break image apart into 8 pixel rows, record each 8-bit column.
-    let w = info.width;
-    let h = info.height;
-    for y_page in 0..(h + 7)/8 {
-        print!("    ");
-        for x in 0..w {
-            let mut c: u8 = 0;
-            for y in 0..8 {
-                let y_total = y_page * 8 + y;
-                if y_total < h && buf[(y_total * w + x) as usize] >= 0x80 {
-                    c |= 1 << y;
-                }
-            }
-            print!("0x{:02x}, ", c);
+
+    // An APNG with more than one frame gets the full animation
+    // treatment: walk every frame, resolve dispose/blend against an
+    // accumulated canvas, and emit a multi-frame table instead. Only
+    // PNG carries animation, so other formats skip straight past this.
+    if loader::is_png(file_name) {
+        let mut decoder = png::Decoder::new(File::open(file_name).unwrap());
+        decoder.set_transformations(png::Transformations::EXPAND);
+        // `read_info` hands back an `OutputInfo`, which doesn't carry
+        // `animation_control` — pull the real `Info` (available once
+        // the header is parsed) via `reader.info()` instead, cloning
+        // it out before the `&mut reader` borrow below.
+        let (_, mut reader) = decoder.read_info().unwrap();
+        let info = reader.info().clone();
+        if info.animation_control.map(|ac| ac.num_frames).unwrap_or(1) > 1 {
+            anim::emit(&mut reader, &info, stem, &args);
+            return;
         }
-        println!();
     }
 
-    println!("}};");
+    let (w, h, buf) = loader::load_grayscale(file_name, args.background);
+    let (w, h, mut buf) = apply_transforms(&args, w, h, buf);
+
+    if let Some(mode) = args.dither_mode {
+        dither::apply(mode, &mut buf, w, h);
+    }
+
+    match args.format {
+        OutputFormat::CArray => {
+            println!("static const char {}[] = {{", stem);
+            pack::print_pages(&buf, w, h, args.addressing);
+            println!("}};");
+        }
+        OutputFormat::Raw => {
+            let packed = pack::to_bytes(&buf, w, h, args.addressing);
+            raw::write(&packed, w, h, args.signed, args.raw_header, args.output.as_deref())
+                .expect("Failed to write raw output");
+        }
+    }
 }
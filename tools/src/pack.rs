@@ -0,0 +1,68 @@
+//
+// Packs an 8-bit grayscale buffer into the page/column byte layout
+// the SSD1306/SSD1780 expects: each byte covers one column of 8
+// vertically-stacked pixels within an 8-row "page".
+//
+
+// SSD1306 GDDRAM auto-increments either across a page before moving
+// to the next (horizontal/"page" addressing, the panel's power-on
+// default) or down a column before moving to the next (vertical
+// addressing). Both read the same 8-pixel-tall byte, just in a
+// different byte order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Addressing {
+    Page,
+    Vertical,
+}
+
+fn page_byte(buf: &[u8], w: u32, h: u32, x: u32, y_page: u32) -> u8 {
+    let mut c: u8 = 0;
+    for y in 0..8 {
+        let y_total = y_page * 8 + y;
+        if y_total < h && buf[(y_total * w + x) as usize] >= 0x80 {
+            c |= 1 << y;
+        }
+    }
+    c
+}
+
+// Packs an 8-bit grayscale buffer into page/column bytes, in the same
+// order the C array and raw-binary output both emit them in.
+pub fn to_bytes(buf: &[u8], w: u32, h: u32, addressing: Addressing) -> Vec<u8> {
+    let pages = (h + 7) / 8;
+    let mut out = Vec::with_capacity((w * pages) as usize);
+    match addressing {
+        Addressing::Page => {
+            for y_page in 0..pages {
+                for x in 0..w {
+                    out.push(page_byte(buf, w, h, x, y_page));
+                }
+            }
+        }
+        Addressing::Vertical => {
+            for x in 0..w {
+                for y_page in 0..pages {
+                    out.push(page_byte(buf, w, h, x, y_page));
+                }
+            }
+        }
+    }
+    out
+}
+
+// Prints the packed bytes for one grayscale frame as C array
+// initializer rows (the caller supplies the surrounding braces). Rows
+// are grouped by `row_len` purely for readability of the output.
+pub fn print_pages(buf: &[u8], w: u32, h: u32, addressing: Addressing) {
+    let row_len = match addressing {
+        Addressing::Page => w as usize,
+        Addressing::Vertical => ((h + 7) / 8) as usize,
+    };
+    for row in to_bytes(buf, w, h, addressing).chunks(row_len) {
+        print!("    ");
+        for &c in row {
+            print!("0x{:02x}, ", c);
+        }
+        println!();
+    }
+}
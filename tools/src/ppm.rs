@@ -0,0 +1,102 @@
+//
+// Minimal binary PPM/PGM decoder: P5 (grayscale) and P6 (RGB),
+// 8-bit or 16-bit samples. No support for the ASCII P1/P2/P3 variants.
+//
+
+use crate::luma::luma;
+
+// Reads the next whitespace-separated token, skipping `#` comments,
+// and returns the byte offset just past it.
+fn next_token(data: &[u8], mut pos: usize) -> (&[u8], usize) {
+    loop {
+        while pos < data.len() && (data[pos] as char).is_whitespace() {
+            pos += 1;
+        }
+        if data[pos] == b'#' {
+            while pos < data.len() && data[pos] != b'\n' {
+                pos += 1;
+            }
+            continue;
+        }
+        break;
+    }
+    let start = pos;
+    while pos < data.len() && !(data[pos] as char).is_whitespace() {
+        pos += 1;
+    }
+    (&data[start..pos], pos)
+}
+
+pub fn load(data: &[u8]) -> (u32, u32, Vec<u8>) {
+    let magic = &data[0..2];
+    assert!(magic == b"P5" || magic == b"P6", "Not a binary PPM/PGM file");
+    let channels = if magic == b"P6" { 3 } else { 1 };
+
+    let (width_tok, pos) = next_token(data, 2);
+    let (height_tok, pos) = next_token(data, pos);
+    let (maxval_tok, pos) = next_token(data, pos);
+
+    let width: u32 = std::str::from_utf8(width_tok).unwrap().parse().unwrap();
+    let height: u32 = std::str::from_utf8(height_tok).unwrap().parse().unwrap();
+    let maxval: u32 = std::str::from_utf8(maxval_tok).unwrap().parse().unwrap();
+    // Exactly one whitespace byte follows maxval before the binary data.
+    let mut body = pos + 1;
+
+    let bytes_per_sample = if maxval > 255 { 2 } else { 1 };
+    let sample = |off: usize| -> u8 {
+        if bytes_per_sample == 1 {
+            ((data[off] as u32 * 255) / maxval) as u8
+        } else {
+            let v = u16::from_be_bytes([data[off], data[off + 1]]) as u32;
+            ((v * 255) / maxval) as u8
+        }
+    };
+
+    let mut out = Vec::with_capacity((width * height) as usize);
+    for _ in 0..(width * height) {
+        let px = if channels == 3 {
+            let r = sample(body);
+            let g = sample(body + bytes_per_sample);
+            let b = sample(body + 2 * bytes_per_sample);
+            luma(r, g, b)
+        } else {
+            sample(body)
+        };
+        out.push(px);
+        body += channels * bytes_per_sample;
+    }
+
+    (width, height, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_p5_grayscale() {
+        let mut data = b"P5\n2 2\n255\n".to_vec();
+        data.extend_from_slice(&[0, 64, 128, 255]);
+        let (w, h, buf) = load(&data);
+        assert_eq!((w, h), (2, 2));
+        assert_eq!(buf, vec![0, 64, 128, 255]);
+    }
+
+    #[test]
+    fn loads_p6_rgb_as_luma() {
+        let mut data = b"P6\n1 1\n255\n".to_vec();
+        data.extend_from_slice(&[100, 100, 100]); // Equal channels: luma == the value.
+        let (w, h, buf) = load(&data);
+        assert_eq!((w, h), (1, 1));
+        assert_eq!(buf, vec![100]);
+    }
+
+    #[test]
+    fn skips_comment_lines_in_header() {
+        let mut data = b"P5\n# a comment\n2 1\n255\n".to_vec();
+        data.extend_from_slice(&[10, 20]);
+        let (w, h, buf) = load(&data);
+        assert_eq!((w, h), (2, 1));
+        assert_eq!(buf, vec![10, 20]);
+    }
+}
@@ -0,0 +1,44 @@
+//
+// Raw binary output: writes the packed page bytes straight to a file
+// (or stdout) instead of wrapping them in a C source array. Matches
+// the `png_to_raw` pattern used by embedded build scripts that bake
+// display assets into the firmware via `build.rs`.
+//
+
+use std::fs::File;
+use std::io::{self, Write};
+
+// Wrapping-subtracts 128 from each byte, for pipelines that expect
+// signed samples rather than unsigned.
+fn to_signed(packed: &[u8]) -> Vec<u8> {
+    packed.iter().map(|&b| b.wrapping_sub(128)).collect()
+}
+
+// Writes `width`/`height` as little-endian u32s ahead of the pixel
+// data, so the consumer can validate geometry before loading it.
+fn write_header<W: Write>(w: &mut W, width: u32, height: u32) -> io::Result<()> {
+    w.write_all(&width.to_le_bytes())?;
+    w.write_all(&height.to_le_bytes())?;
+    Ok(())
+}
+
+pub fn write(
+    packed: &[u8],
+    width: u32,
+    height: u32,
+    signed: bool,
+    header: bool,
+    output: Option<&str>,
+) -> io::Result<()> {
+    let packed = if signed { to_signed(packed) } else { packed.to_vec() };
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+
+    if header {
+        write_header(&mut out, width, height)?;
+    }
+    out.write_all(&packed)
+}
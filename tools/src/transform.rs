@@ -0,0 +1,177 @@
+//
+// Geometric transforms applied to the grayscale buffer before
+// packing, so users can target panels mounted in different
+// orientations without re-authoring the source image.
+//
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    R0,
+    R90,
+    R180,
+    R270,
+}
+
+impl Rotation {
+    pub fn from_str(s: &str) -> Option<Rotation> {
+        match s {
+            "0" => Some(Rotation::R0),
+            "90" => Some(Rotation::R90),
+            "180" => Some(Rotation::R180),
+            "270" => Some(Rotation::R270),
+            _ => None,
+        }
+    }
+}
+
+// The width/height a buffer ends up with after `rotate`, without
+// needing the pixel data itself.
+pub fn rotated_dims(rotation: Rotation, w: u32, h: u32) -> (u32, u32) {
+    match rotation {
+        Rotation::R0 | Rotation::R180 => (w, h),
+        Rotation::R90 | Rotation::R270 => (h, w),
+    }
+}
+
+// Rotates clockwise by the given amount, swapping width/height for
+// the two odd multiples of 90 degrees.
+pub fn rotate(rotation: Rotation, w: u32, h: u32, buf: &[u8]) -> (u32, u32, Vec<u8>) {
+    match rotation {
+        Rotation::R0 => (w, h, buf.to_vec()),
+        Rotation::R180 => {
+            let mut out = vec![0u8; buf.len()];
+            for y in 0..h {
+                for x in 0..w {
+                    out[((h - 1 - y) * w + (w - 1 - x)) as usize] = buf[(y * w + x) as usize];
+                }
+            }
+            (w, h, out)
+        }
+        Rotation::R90 => {
+            let mut out = vec![0u8; buf.len()];
+            for y in 0..h {
+                for x in 0..w {
+                    // (x, y) in the source lands at (h-1-y, x) in the
+                    // rotated (now w=h_old, h=w_old) image.
+                    out[(x * h + (h - 1 - y)) as usize] = buf[(y * w + x) as usize];
+                }
+            }
+            (h, w, out)
+        }
+        Rotation::R270 => {
+            let mut out = vec![0u8; buf.len()];
+            for y in 0..h {
+                for x in 0..w {
+                    out[((w - 1 - x) * h + y) as usize] = buf[(y * w + x) as usize];
+                }
+            }
+            (h, w, out)
+        }
+    }
+}
+
+pub fn flip_h(w: u32, h: u32, buf: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; buf.len()];
+    for y in 0..h {
+        for x in 0..w {
+            out[(y * w + (w - 1 - x)) as usize] = buf[(y * w + x) as usize];
+        }
+    }
+    out
+}
+
+pub fn flip_v(w: u32, h: u32, buf: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; buf.len()];
+    for y in 0..h {
+        for x in 0..w {
+            out[((h - 1 - y) * w + x) as usize] = buf[(y * w + x) as usize];
+        }
+    }
+    out
+}
+
+pub fn invert(buf: &mut [u8]) {
+    for b in buf.iter_mut() {
+        *b = 255 - *b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2 wide, 3 tall:
+    // 0 1
+    // 2 3
+    // 4 5
+    const W: u32 = 2;
+    const H: u32 = 3;
+    const BUF: [u8; 6] = [0, 1, 2, 3, 4, 5];
+
+    #[test]
+    fn rotate_0_is_identity() {
+        let (w, h, out) = rotate(Rotation::R0, W, H, &BUF);
+        assert_eq!((w, h), (W, H));
+        assert_eq!(out, BUF);
+    }
+
+    #[test]
+    fn rotate_90_clockwise() {
+        let (w, h, out) = rotate(Rotation::R90, W, H, &BUF);
+        assert_eq!((w, h), (H, W));
+        // 4 2 0
+        // 5 3 1
+        assert_eq!(out, vec![4, 2, 0, 5, 3, 1]);
+    }
+
+    #[test]
+    fn rotate_180() {
+        let (w, h, out) = rotate(Rotation::R180, W, H, &BUF);
+        assert_eq!((w, h), (W, H));
+        assert_eq!(out, vec![5, 4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn rotate_270_clockwise() {
+        let (w, h, out) = rotate(Rotation::R270, W, H, &BUF);
+        assert_eq!((w, h), (H, W));
+        // 1 3 5
+        // 0 2 4
+        assert_eq!(out, vec![1, 3, 5, 0, 2, 4]);
+    }
+
+    #[test]
+    fn rotate_90_then_270_is_identity() {
+        let (w1, h1, mid) = rotate(Rotation::R90, W, H, &BUF);
+        let (w2, h2, back) = rotate(Rotation::R270, w1, h1, &mid);
+        assert_eq!((w2, h2), (W, H));
+        assert_eq!(back, BUF);
+    }
+
+    #[test]
+    fn rotated_dims_matches_rotate() {
+        for r in [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270] {
+            let (w, h, _) = rotate(r, W, H, &BUF);
+            assert_eq!(rotated_dims(r, W, H), (w, h));
+        }
+    }
+
+    #[test]
+    fn flip_h_mirrors_each_row() {
+        let buf = [0u8, 1, 2, 3];
+        assert_eq!(flip_h(2, 2, &buf), vec![1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn flip_v_mirrors_each_column() {
+        let buf = [0u8, 1, 2, 3];
+        assert_eq!(flip_v(2, 2, &buf), vec![2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn invert_complements_each_byte() {
+        let mut buf = [0u8, 128, 255];
+        invert(&mut buf);
+        assert_eq!(buf, [255, 127, 0]);
+    }
+}